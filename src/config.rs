@@ -0,0 +1,162 @@
+//! Loads `devon.toml`, the config file that declares which diagnostic
+//! providers to run and how to parse their output.
+
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub(crate) struct Config {
+    #[serde(default, rename = "provider")]
+    pub(crate) providers: Vec<ProviderConfig>,
+    #[serde(default = "default_theme")]
+    pub(crate) theme: String,
+}
+
+fn default_theme() -> String {
+    "base16-ocean.dark".to_string()
+}
+
+#[derive(Deserialize)]
+pub(crate) struct ProviderConfig {
+    pub(crate) name: String,
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+    #[serde(flatten)]
+    pub(crate) parse: ParseMode,
+    /// External formatter invoked for the `f` quick-fix keybinding on a
+    /// diagnostic from this (non-LSP) provider; its unified diff against
+    /// the file on disk is parsed into the same `fix::Edit` representation
+    /// an LSP `codeAction` produces. Meaningless for `mode = "lsp"`
+    /// providers, whose quick fix comes from `codeAction` instead.
+    #[serde(default)]
+    pub(crate) formatter: Option<FormatterConfig>,
+    /// File extensions (without the leading dot) this provider cares about,
+    /// so `main::relint` can skip it on a `--watch` tick whose changed paths
+    /// don't touch any of them. `None` means "always rerun", for providers
+    /// (or output formats) where that isn't known up front.
+    #[serde(default)]
+    pub(crate) extensions: Option<Vec<String>>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct FormatterConfig {
+    pub(crate) command: String,
+    #[serde(default)]
+    pub(crate) args: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "mode", rename_all = "lowercase")]
+pub(crate) enum ParseMode {
+    /// Diagnostics come from an `lsp::LspClient` kept open for the session,
+    /// not a one-shot subprocess; see `main::spawn_lsp_clients`.
+    Lsp(LspParseConfig),
+    Json(JsonParseConfig),
+    Regex(RegexParseConfig),
+}
+
+/// The handful of things a long-lived LSP provider needs that a one-shot
+/// subprocess provider doesn't: what to tell the server a file is
+/// (`languageId`), and which files under the workspace root to `didOpen`
+/// against it in the first place.
+#[derive(Deserialize)]
+pub(crate) struct LspParseConfig {
+    pub(crate) language_id: String,
+    /// File extensions (without the leading dot) this server should have
+    /// opened, e.g. `["py"]` for pyright.
+    pub(crate) extensions: Vec<String>,
+}
+
+/// Maps a jq-like dotted path in the tool's JSON output to `Diagnostic`
+/// fields.
+#[derive(Deserialize)]
+pub(crate) struct JsonParseConfig {
+    pub(crate) diagnostics_path: String,
+    pub(crate) file_field: String,
+    pub(crate) line_field: String,
+    pub(crate) character_field: String,
+    pub(crate) severity_field: String,
+    pub(crate) message_field: String,
+    #[serde(default)]
+    pub(crate) rule_field: Option<String>,
+}
+
+/// Parses one line of output per diagnostic with a named-capture regex,
+/// e.g. flake8's `path:row:col: CODE msg`.
+#[derive(Deserialize)]
+pub(crate) struct RegexParseConfig {
+    pub(crate) pattern: String,
+    /// Maps the first character of the matched `code` capture to a
+    /// severity name (`"error"`, `"warning"`, or `"information"`).
+    #[serde(default)]
+    pub(crate) severity_map: HashMap<char, String>,
+}
+
+impl Config {
+    /// Loads `path`, falling back to the built-in pyright+flake8 config if
+    /// the file doesn't exist or fails to parse.
+    pub(crate) fn load(path: &std::path::Path) -> Self {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => toml::from_str(&contents).unwrap_or_else(|e| {
+                eprintln!("{}: {e}", path.display());
+                Config::default()
+            }),
+            Err(_) => Config::default(),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            theme: default_theme(),
+            providers: vec![
+                ProviderConfig {
+                    name: "pyright".to_string(),
+                    command: "pyright".to_string(),
+                    args: vec!["--outputjson".to_string(), ".".to_string()],
+                    parse: ParseMode::Json(JsonParseConfig {
+                        diagnostics_path: "generalDiagnostics".to_string(),
+                        file_field: "file".to_string(),
+                        line_field: "range.start.line".to_string(),
+                        character_field: "range.start.character".to_string(),
+                        severity_field: "severity".to_string(),
+                        message_field: "message".to_string(),
+                        rule_field: Some("rule".to_string()),
+                    }),
+                    formatter: None,
+                    extensions: Some(vec!["py".to_string()]),
+                },
+                ProviderConfig {
+                    name: "flake8".to_string(),
+                    command: "flake8".to_string(),
+                    args: vec![".".to_string()],
+                    parse: ParseMode::Regex(RegexParseConfig {
+                        pattern: r"^(?P<path>[^:]+):(?P<row>\d+):(?P<col>\d+): (?P<code>\S+) (?P<msg>.+)$"
+                            .to_string(),
+                        severity_map: [
+                            ('E', "error"),
+                            ('W', "warning"),
+                            ('F', "error"),
+                            ('C', "information"),
+                            ('N', "warning"),
+                        ]
+                        .into_iter()
+                        .map(|(code, severity)| (code, severity.to_string()))
+                        .collect(),
+                    }),
+                    // `black --diff` reports what it would change without
+                    // touching the file; `run_formatter` parses that diff
+                    // into a `Fix` for the `f` keybinding.
+                    formatter: Some(FormatterConfig {
+                        command: "black".to_string(),
+                        args: vec!["--diff".to_string()],
+                    }),
+                    extensions: Some(vec!["py".to_string()]),
+                },
+            ],
+        }
+    }
+}
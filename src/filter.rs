@@ -0,0 +1,58 @@
+//! Fuzzy subsequence matching for the diagnostic filter bar (`/`):
+//! characters of the query must appear in order in the haystack, with
+//! bonuses for prefix and contiguous matches so the tightest matches sort
+//! first.
+
+use crate::Diagnostic;
+
+/// Returns a match score, or `None` if `query`'s characters don't all
+/// appear in `haystack` in order. Higher scores rank first; an empty query
+/// matches everything with score 0.
+pub(crate) fn fuzzy_score(haystack: &str, query: &str) -> Option<i32> {
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let hay: Vec<char> = haystack.to_lowercase().chars().collect();
+    let q: Vec<char> = query.to_lowercase().chars().collect();
+
+    let mut score = 0i32;
+    let mut hay_idx = 0;
+    let mut prev_match: Option<usize> = None;
+
+    for &qc in &q {
+        let idx = loop {
+            if hay_idx >= hay.len() {
+                return None;
+            }
+            if hay[hay_idx] == qc {
+                break hay_idx;
+            }
+            hay_idx += 1;
+        };
+
+        if idx == 0 {
+            score += 10;
+        }
+        if prev_match == Some(idx.wrapping_sub(1)) {
+            score += 5;
+        }
+
+        prev_match = Some(idx);
+        hay_idx = idx + 1;
+    }
+
+    Some(score)
+}
+
+/// Best score for `diag` across its message, file path, and rule code.
+pub(crate) fn score_diagnostic(diag: &Diagnostic, query: &str) -> Option<i32> {
+    [
+        fuzzy_score(&diag.message, query),
+        fuzzy_score(&diag.file, query),
+        diag.rule.as_deref().and_then(|rule| fuzzy_score(rule, query)),
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+}
@@ -0,0 +1,100 @@
+//! Applying textual fixes: validating a set of byte-range edits, applying
+//! them to a source string, and writing the patched file back to disk.
+
+use std::ops::Range;
+
+/// One text edit: replace the bytes in `range` of the original source with
+/// `replacement`.
+#[derive(Clone, Debug)]
+pub(crate) struct Edit {
+    pub(crate) range: Range<usize>,
+    pub(crate) replacement: String,
+}
+
+/// A fix applies a set of edits to a single file.
+#[derive(Clone, Debug)]
+pub(crate) struct Fix {
+    pub(crate) file: String,
+    pub(crate) edits: Vec<Edit>,
+}
+
+#[derive(Debug)]
+pub(crate) enum FixError {
+    OverlappingEdits,
+}
+
+impl std::fmt::Display for FixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OverlappingEdits => write!(f, "fix contains overlapping edits"),
+        }
+    }
+}
+
+impl std::error::Error for FixError {}
+
+/// Applies `edits` to `source`, returning the patched string.
+///
+/// Edits are applied back-to-front by descending start offset, so earlier
+/// edits never invalidate the byte offsets of later ones. An edit set
+/// containing two overlapping ranges is rejected rather than applied in an
+/// arbitrary order.
+pub(crate) fn apply_edits(source: &str, edits: &[Edit]) -> Result<String, FixError> {
+    let mut sorted: Vec<&Edit> = edits.iter().collect();
+    sorted.sort_by(|a, b| b.range.start.cmp(&a.range.start));
+
+    for pair in sorted.windows(2) {
+        let (later, earlier) = (pair[0], pair[1]);
+        if earlier.range.end > later.range.start {
+            return Err(FixError::OverlappingEdits);
+        }
+    }
+
+    let mut result = source.to_string();
+    for edit in sorted {
+        result.replace_range(edit.range.clone(), &edit.replacement);
+    }
+    Ok(result)
+}
+
+/// Applies `fix` to its target file on disk.
+pub(crate) fn apply_fix(fix: &Fix) -> std::io::Result<()> {
+    let source = std::fs::read_to_string(&fix.file)?;
+    let patched = apply_edits(&source, &fix.edits)
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+    std::fs::write(&fix.file, patched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(range: Range<usize>, replacement: &str) -> Edit {
+        Edit {
+            range,
+            replacement: replacement.to_string(),
+        }
+    }
+
+    #[test]
+    fn applies_edits_out_of_order_by_descending_start() {
+        // Edits are given front-to-back; applying them in that order would
+        // shift every later range once the first replacement changes length.
+        let edits = vec![edit(0..5, "hi"), edit(6..11, "there")];
+        let result = apply_edits("hello world", &edits).unwrap();
+        assert_eq!(result, "hi there");
+    }
+
+    #[test]
+    fn applies_adjacent_non_overlapping_edits() {
+        let edits = vec![edit(0..5, "hi"), edit(5..11, " earth")];
+        let result = apply_edits("hello world", &edits).unwrap();
+        assert_eq!(result, "hi earth");
+    }
+
+    #[test]
+    fn rejects_overlapping_edits() {
+        let edits = vec![edit(0..6, "a"), edit(4..11, "b")];
+        assert!(matches!(apply_edits("hello world", &edits), Err(FixError::OverlappingEdits)));
+    }
+}
@@ -0,0 +1,109 @@
+//! Syntax-highlights diagnostic source context with `syntect` before it is
+//! handed to ariadne, so the code surrounding an error is readable at a
+//! glance instead of plain text.
+//!
+//! `SyntaxSet`/`ThemeSet` are expensive to build, so each is loaded once
+//! and cached for the process lifetime.
+
+use std::sync::OnceLock;
+
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxSet;
+use syntect::util::{as_24_bit_terminal_escaped, LinesWithEndings};
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SET: OnceLock<SyntaxSet> = OnceLock::new();
+    SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+    static SET: OnceLock<ThemeSet> = OnceLock::new();
+    SET.get_or_init(ThemeSet::load_defaults)
+}
+
+/// Highlights `source` using the syntax for `file`'s extension and the
+/// named `theme`, returning the same text with ANSI SGR codes embedded so
+/// ariadne's own snippet rendering carries the color straight through. If
+/// the extension or theme is unrecognized, `source` is returned unchanged.
+pub(crate) fn highlight_source(source: &str, file: &str, theme: &str) -> String {
+    let syntaxes = syntax_set();
+    let extension = std::path::Path::new(file)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("");
+    let Some(syntax) = syntaxes.find_syntax_by_extension(extension) else {
+        return source.to_string();
+    };
+    let Some(theme) = theme_set().themes.get(theme) else {
+        return source.to_string();
+    };
+
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut out = String::with_capacity(source.len());
+    for line in LinesWithEndings::from(source) {
+        match highlighter.highlight_line(line, syntaxes) {
+            Ok(ranges) => out.push_str(&as_24_bit_terminal_escaped(&ranges[..], false)),
+            Err(_) => out.push_str(line),
+        }
+    }
+    out
+}
+
+/// Byte offset of (`line`, `character`) within `text`, where `character` is
+/// a count of *visible* characters on that line — ANSI escape sequences
+/// inserted by `highlight_source` are skipped rather than counted, so
+/// diagnostic ranges computed against the plain source still line up after
+/// highlighting.
+pub(crate) fn byte_offset(text: &str, line: usize, character: usize) -> usize {
+    let mut offset = 0;
+    for (index, text_line) in text.split_inclusive('\n').enumerate() {
+        if index == line {
+            return offset + visible_byte_offset(text_line, character);
+        }
+        offset += text_line.len();
+    }
+    offset
+}
+
+/// Byte offset of the end of `line`'s visible content (i.e. just before its
+/// trailing newline, if any). Used to extend a zero-width diagnostic range
+/// to the rest of the line.
+pub(crate) fn line_end_offset(text: &str, line: usize) -> usize {
+    let mut offset = 0;
+    for (index, text_line) in text.split_inclusive('\n').enumerate() {
+        let trimmed = text_line.strip_suffix('\n').unwrap_or(text_line);
+        if index == line {
+            return offset + trimmed.len();
+        }
+        offset += text_line.len();
+    }
+    offset
+}
+
+fn visible_byte_offset(line: &str, visible_char: usize) -> usize {
+    let mut seen = 0;
+    let mut chars = line.char_indices().peekable();
+    while let Some(&(byte, ch)) = chars.peek() {
+        if ch == '\u{1b}' {
+            // Skip a full CSI escape sequence: ESC '[' ... final byte in 0x40..=0x7e.
+            chars.next();
+            if chars.peek().is_some_and(|&(_, c)| c == '[') {
+                chars.next();
+            }
+            while let Some(&(_, c)) = chars.peek() {
+                chars.next();
+                if ('\x40'..='\x7e').contains(&c) {
+                    break;
+                }
+            }
+            continue;
+        }
+        if seen == visible_char {
+            return byte;
+        }
+        seen += 1;
+        chars.next();
+    }
+    line.len()
+}
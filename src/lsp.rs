@@ -0,0 +1,444 @@
+//! A minimal LSP client: spawns a language server over stdio, speaks
+//! Content-Length framed JSON-RPC, and turns `textDocument/publishDiagnostics`
+//! notifications into the crate's own `Diagnostic` type.
+//!
+//! It drives the initialize handshake, `didOpen`, diagnostics collection,
+//! and `textDocument/codeAction` requests for quick fixes. Incremental sync
+//! (`didChange`) is not sent.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::fix::{Edit, Fix};
+use crate::{AppEvent, Diagnostic, Location, Range, Severity};
+
+#[derive(Deserialize)]
+struct Incoming {
+    #[serde(default)]
+    id: Option<u64>,
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Option<Value>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<IncomingError>,
+}
+
+#[derive(Deserialize)]
+struct IncomingError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LspTextEdit {
+    range: LspRange,
+    new_text: String,
+}
+
+#[derive(Deserialize)]
+struct LspWorkspaceEdit {
+    #[serde(default)]
+    changes: HashMap<String, Vec<LspTextEdit>>,
+}
+
+#[derive(Deserialize)]
+struct LspCodeAction {
+    #[serde(default)]
+    edit: Option<LspWorkspaceEdit>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LspDiagnostic {
+    range: LspRange,
+    severity: Option<u8>,
+    message: String,
+    code: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct LspRange {
+    start: LspPosition,
+    end: LspPosition,
+}
+
+#[derive(Deserialize)]
+struct LspPosition {
+    line: usize,
+    character: usize,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PublishDiagnosticsParams {
+    uri: String,
+    diagnostics: Vec<LspDiagnostic>,
+}
+
+fn severity_from_lsp(severity: Option<u8>) -> Severity {
+    match severity {
+        Some(1) => Severity::Error,
+        Some(2) => Severity::Warning,
+        _ => Severity::Information,
+    }
+}
+
+fn uri_to_path(uri: &str) -> String {
+    uri.strip_prefix("file://").unwrap_or(uri).to_string()
+}
+
+/// Recursively finds files under `root` whose extension is in `extensions`,
+/// so `main::spawn_lsp_clients` can `didOpen` a server's workspace up
+/// front. Hidden directories (`.git`, `.venv`, ...) are skipped.
+pub(crate) fn discover_files(root: &std::path::Path, extensions: &[String]) -> Vec<String> {
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            files.extend(discover_files(&path, extensions));
+        } else if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
+            if extensions.iter().any(|e| e == ext) {
+                files.push(path.to_string_lossy().into_owned());
+            }
+        }
+    }
+    files
+}
+
+/// A connection to one language server process.
+///
+/// Diagnostics published by the server are decoded on a background reader
+/// thread and handed back through `try_recv_diagnostics`.
+pub(crate) struct LspClient {
+    child: Child,
+    stdin: ChildStdin,
+    next_id: u64,
+    /// Name of the `devon.toml` provider this client was spawned for, so
+    /// `main::resolve_fix` can find the client that owns a given
+    /// `Diagnostic` by matching it against `Diagnostic::provider`.
+    provider_name: String,
+    /// Per-file open-document version counter, bumped on each `didChange`
+    /// (not sent yet, but the counter is seeded here for when it is).
+    versions: HashMap<String, i32>,
+    diagnostics: Receiver<(String, Vec<Diagnostic>)>,
+    /// `(id, result)` pairs for request responses, `result` being `Err` with
+    /// the server's error message for a JSON-RPC error response. `initialize`
+    /// and `code_action` each send one request, then synchronously read the
+    /// next entry off this channel, so matching it against the request that
+    /// just went out is sufficient without tracking ids separately.
+    responses: Receiver<(u64, Result<Value, String>)>,
+}
+
+impl LspClient {
+    pub(crate) fn provider_name(&self) -> &str {
+        &self.provider_name
+    }
+}
+
+impl LspClient {
+    /// Spawns `cmd` with `args` and performs the `initialize`/`initialized`
+    /// handshake against `root`. Diagnostics this server publishes are
+    /// tagged with `provider_name` (so `main::resolve_fix` can route a
+    /// quick-fix request back to the client that found it), and `app_tx`
+    /// gets an `AppEvent::FilesChanged` nudge each time a fresh batch
+    /// arrives, so the main loop re-renders without waiting on a filesystem
+    /// event.
+    pub(crate) fn spawn(
+        cmd: &str,
+        args: &[String],
+        root: &str,
+        provider_name: &str,
+        app_tx: Sender<AppEvent>,
+    ) -> std::io::Result<Self> {
+        let mut child = Command::new(cmd)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdin = child.stdin.take().unwrap();
+        let stdout = child.stdout.take().unwrap();
+
+        let (diagnostics_tx, diagnostics_rx) = channel();
+        let (responses_tx, responses_rx) = channel();
+        let provider_name = provider_name.to_string();
+        std::thread::spawn({
+            let provider_name = provider_name.clone();
+            move || read_loop(stdout, diagnostics_tx, responses_tx, app_tx, provider_name)
+        });
+
+        let mut client = Self {
+            child,
+            stdin,
+            next_id: 1,
+            provider_name,
+            versions: HashMap::new(),
+            diagnostics: diagnostics_rx,
+            responses: responses_rx,
+        };
+        client.initialize(root)?;
+        Ok(client)
+    }
+
+    fn write_message(&mut self, body: &Value) -> std::io::Result<()> {
+        let payload = serde_json::to_vec(body)?;
+        write!(self.stdin, "Content-Length: {}\r\n\r\n", payload.len())?;
+        self.stdin.write_all(&payload)?;
+        self.stdin.flush()
+    }
+
+    fn request(&mut self, method: &'static str, params: Value) -> std::io::Result<u64> {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.write_message(&json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        }))?;
+        Ok(id)
+    }
+
+    fn notify(&mut self, method: &'static str, params: Value) -> std::io::Result<()> {
+        self.write_message(&json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        }))
+    }
+
+    fn initialize(&mut self, root: &str) -> std::io::Result<()> {
+        let id = self.request(
+            "initialize",
+            json!({
+                "processId": std::process::id(),
+                "rootUri": format!("file://{}", root),
+                "capabilities": {},
+            }),
+        )?;
+        // Drain the response off `responses` so it doesn't sit there and get
+        // mistaken for the first `code_action()` call's response later on.
+        let (response_id, result) = self
+            .responses
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "no response from language server"))?;
+        debug_assert_eq!(response_id, id);
+        result.map_err(|message| std::io::Error::new(std::io::ErrorKind::Other, message))?;
+        self.notify("initialized", json!({}))
+    }
+
+    /// Sends `textDocument/didOpen` for `path`, seeding the version counter
+    /// the server expects on any later edits to the same file.
+    pub(crate) fn did_open(&mut self, path: &str, language_id: &str) -> std::io::Result<()> {
+        let text = std::fs::read_to_string(path)?;
+        let version = *self.versions.entry(path.to_string()).or_insert(0);
+        self.versions.insert(path.to_string(), version + 1);
+        self.notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": format!("file://{}", path),
+                    "languageId": language_id,
+                    "version": version,
+                    "text": text,
+                }
+            }),
+        )
+    }
+
+    /// Drains whatever `publishDiagnostics` notifications have arrived so
+    /// far, without blocking.
+    pub(crate) fn try_recv_diagnostics(&self) -> Vec<(String, Vec<Diagnostic>)> {
+        self.diagnostics.try_iter().collect()
+    }
+
+    /// Requests quick fixes for `diagnostic` in `file` and turns the first
+    /// `WorkspaceEdit` the server returns into a `Fix`.
+    pub(crate) fn code_action(&mut self, file: &str, diagnostic: &Diagnostic) -> std::io::Result<Option<Fix>> {
+        let id = self.request(
+            "textDocument/codeAction",
+            json!({
+                "textDocument": { "uri": format!("file://{}", file) },
+                "range": lsp_range(&diagnostic.range),
+                "context": { "diagnostics": [lsp_diagnostic(diagnostic)] },
+            }),
+        )?;
+
+        let (response_id, result) = self
+            .responses
+            .recv_timeout(std::time::Duration::from_secs(5))
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::TimedOut, "no response from language server"))?;
+        debug_assert_eq!(response_id, id);
+        let result = result.map_err(|message| std::io::Error::new(std::io::ErrorKind::Other, message))?;
+
+        let Ok(actions) = serde_json::from_value::<Vec<LspCodeAction>>(result) else {
+            return Ok(None);
+        };
+        let Some(edit) = actions.into_iter().find_map(|a| a.edit) else {
+            return Ok(None);
+        };
+        let Some((uri, text_edits)) = edit.changes.into_iter().next() else {
+            return Ok(None);
+        };
+
+        lsp_edits_to_fix(&uri_to_path(&uri), text_edits).map(Some)
+    }
+}
+
+fn lsp_range(range: &Range) -> Value {
+    json!({
+        "start": { "line": range.start.line, "character": range.start.character },
+        "end": { "line": range.end.line, "character": range.end.character },
+    })
+}
+
+fn lsp_diagnostic(diagnostic: &Diagnostic) -> Value {
+    json!({
+        "range": lsp_range(&diagnostic.range),
+        "severity": match diagnostic.severity {
+            Severity::Error => 1,
+            Severity::Warning => 2,
+            Severity::Information => 3,
+        },
+        "message": diagnostic.message,
+        "code": diagnostic.rule,
+    })
+}
+
+fn lsp_edits_to_fix(file: &str, edits: Vec<LspTextEdit>) -> std::io::Result<Fix> {
+    let source = ariadne::Source::from(std::fs::read_to_string(file)?);
+    let edits = edits
+        .into_iter()
+        .map(|e| {
+            let start = Location {
+                line: e.range.start.line,
+                character: e.range.start.character,
+            }
+            .to_byte_offset(&source);
+            let end = Location {
+                line: e.range.end.line,
+                character: e.range.end.character,
+            }
+            .to_byte_offset(&source);
+            Edit {
+                range: start..end,
+                replacement: e.new_text,
+            }
+        })
+        .collect();
+    Ok(Fix {
+        file: file.to_string(),
+        edits,
+    })
+}
+
+impl Drop for LspClient {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+    }
+}
+
+fn read_loop(
+    stdout: impl Read,
+    diagnostics_tx: Sender<(String, Vec<Diagnostic>)>,
+    responses_tx: Sender<(u64, Result<Value, String>)>,
+    app_tx: Sender<AppEvent>,
+    provider_name: String,
+) {
+    let mut reader = BufReader::new(stdout);
+    while let Some(body) = read_framed_message(&mut reader) {
+        let Ok(msg) = serde_json::from_slice::<Incoming>(&body) else {
+            continue;
+        };
+
+        // A message with both an `id` and a `method` is a request from the
+        // server, not a response to one of ours.
+        if let (Some(id), None) = (msg.id, msg.method.as_ref()) {
+            let result = match (msg.result, msg.error) {
+                (Some(result), _) => Ok(result),
+                (None, Some(error)) => Err(error.message),
+                (None, None) => Err("response has neither result nor error".to_string()),
+            };
+            if responses_tx.send((id, result)).is_err() {
+                return;
+            }
+            continue;
+        }
+
+        if msg.method.as_deref() != Some("textDocument/publishDiagnostics") {
+            continue;
+        }
+        let Some(params) = msg.params else { continue };
+        let Ok(params) = serde_json::from_value::<PublishDiagnosticsParams>(params) else {
+            continue;
+        };
+
+        let path = uri_to_path(&params.uri);
+        let diagnostics = params
+            .diagnostics
+            .into_iter()
+            .map(|d| Diagnostic {
+                file: path.clone(),
+                severity: severity_from_lsp(d.severity),
+                message: d.message,
+                range: Range {
+                    start: Location {
+                        line: d.range.start.line,
+                        character: d.range.start.character,
+                    },
+                    end: Location {
+                        line: d.range.end.line,
+                        character: d.range.end.character,
+                    },
+                },
+                rule: d.code.map(|code| match code {
+                    Value::String(s) => s,
+                    other => other.to_string(),
+                }),
+                provider: provider_name.clone(),
+            })
+            .collect();
+
+        if diagnostics_tx.send((path, diagnostics)).is_err() {
+            return;
+        }
+        let _ = app_tx.send(AppEvent::FilesChanged(Vec::new()));
+    }
+}
+
+/// Reads one `Content-Length`-framed JSON-RPC message, or `None` on EOF.
+fn read_framed_message(reader: &mut impl BufRead) -> Option<Vec<u8>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length: ") {
+            content_length = value.trim().parse::<usize>().ok();
+        }
+    }
+    let content_length = content_length?;
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(body)
+}
@@ -1,60 +1,83 @@
+use std::collections::HashMap;
 use std::io::Write;
 
 use ariadne::{Label, ReportKind, Source};
 use crossterm::{
-    cursor::{self, MoveRight, MoveTo, MoveToNextLine},
+    cursor,
     event::{Event, KeyCode, KeyModifiers},
-    style::{Color, Colors, ResetColor, SetColors},
-    terminal::{
-        disable_raw_mode, enable_raw_mode, Clear, ClearType, DisableLineWrap, EnterAlternateScreen,
-        LeaveAlternateScreen, ScrollDown,
-    },
+    terminal::{disable_raw_mode, enable_raw_mode, DisableLineWrap, EnterAlternateScreen, LeaveAlternateScreen},
     QueueableCommand,
 };
-use serde::Deserialize;
-
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct Output {
-    general_diagnostics: Vec<Diagnostic>,
-    // summary: Summary,
+use ratatui::backend::CrosstermBackend;
+use ratatui::Terminal;
+
+mod config;
+mod filter;
+mod fix;
+mod highlight;
+mod lsp;
+mod providers;
+mod ui;
+mod watch;
+
+/// Events multiplexed into the main loop: key presses and terminal resizes
+/// from crossterm, filesystem-change ticks from `watch` when `--watch` is
+/// enabled (carrying the paths that changed, so `relint` can skip
+/// providers that don't care about them), and diagnostics ticks from an
+/// `lsp::LspClient` (carrying no paths, since nothing on disk changed).
+pub(crate) enum AppEvent {
+    Key(crossterm::event::KeyEvent),
+    Resize(u16, u16),
+    FilesChanged(Vec<String>),
 }
 
-#[derive(Deserialize)]
-struct Diagnostic {
-    file: String,
-    severity: Severity,
-    message: String,
-    range: Range,
-    rule: Option<String>,
+#[derive(Clone)]
+pub(crate) struct Diagnostic {
+    pub(crate) file: String,
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    pub(crate) range: Range,
+    pub(crate) rule: Option<String>,
+    /// Name of the provider that produced this diagnostic, e.g. `"pyright"`
+    /// or `"flake8"`.
+    pub(crate) provider: String,
 }
 
-#[derive(Deserialize)]
-struct Range {
-    start: Location,
-    end: Location,
+#[derive(Clone)]
+pub(crate) struct Range {
+    pub(crate) start: Location,
+    pub(crate) end: Location,
 }
 
-#[derive(Debug, Deserialize)]
-struct Location {
-    line: usize,
-    character: usize,
+#[derive(Clone, Debug)]
+pub(crate) struct Location {
+    pub(crate) line: usize,
+    pub(crate) character: usize,
 }
 
 impl Location {
-    fn to_byte_offset(&self, source: &Source) -> usize {
+    pub(crate) fn to_byte_offset(&self, source: &Source) -> usize {
         source.line(self.line).unwrap().offset() + self.character
     }
 }
 
-#[derive(Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum Severity {
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum Severity {
     Error,
     Warning,
     Information,
 }
 
+impl Severity {
+    pub(crate) fn label(&self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warning => "warning",
+            Self::Information => "information",
+        }
+    }
+}
+
 impl Severity {
     fn to_report_kind(&self) -> ReportKind {
         match self {
@@ -65,207 +88,366 @@ impl Severity {
     }
 }
 
-#[derive(Deserialize)]
-struct Summary {}
-
-fn pyright() -> Vec<Item> {
-    let out = std::process::Command::new("pyright")
-        .arg("--outputjson")
-        .arg(".")
-        .output()
-        .expect("No pyright in $PATH");
-    if out.stdout.len() == 0 {
-        return Vec::new();
+/// Renders one `Diagnostic` through ariadne into an `Item`'s lines.
+///
+/// The source is syntax-highlighted with `highlight::highlight_source`
+/// before being handed to ariadne, so diagnostic byte offsets are computed
+/// against the highlighted (ANSI-laden) text via `highlight::byte_offset`
+/// rather than the plain-text `Location::to_byte_offset`.
+///
+/// A provider that only reports a point rather than a range (flake8's
+/// `row:col`) ends up with `range.start == range.end`; in that case the
+/// label is extended to the rest of the line so there's something to
+/// underline.
+///
+/// Returns `None` if `diag.file` can no longer be read, which happens in
+/// `--watch` mode when a diagnostic outlives the file it was reported
+/// against (deleted or renamed away before the next tick); `relint` drops
+/// such diagnostics instead of rendering them.
+fn diagnostic_to_item(diag: &Diagnostic, theme: &str) -> Option<Item> {
+    let raw = std::fs::read_to_string(&diag.file).ok()?;
+    let highlighted = highlight::highlight_source(&raw, &diag.file, theme);
+
+    let start = highlight::byte_offset(&highlighted, diag.range.start.line, diag.range.start.character);
+    let mut end = highlight::byte_offset(&highlighted, diag.range.end.line, diag.range.end.character);
+    if end <= start {
+        end = highlight::line_end_offset(&highlighted, diag.range.start.line);
     }
-    let output: Output = serde_json::from_slice(&out.stdout).unwrap();
-    let mut items = vec![];
-    // let mut reports = vec![];
-    for item in output.general_diagnostics {
-        let source = Source::from(std::fs::read_to_string(&item.file).unwrap());
-
-        let mut buf = Vec::<u8>::new();
-
-        ariadne::Report::build(
-            item.severity.to_report_kind(),
-            &item.file,
-            item.range.start.to_byte_offset(&source),
-        )
-        .with_message(format!("[pyright] {}", item.rule.unwrap_or_default()))
-        .with_label(
-            Label::new((
-                &item.file,
-                item.range.start.to_byte_offset(&source)..item.range.end.to_byte_offset(&source),
-            ))
-            .with_message(item.message),
-        )
+
+    let mut buf = Vec::<u8>::new();
+    ariadne::Report::<(&str, std::ops::Range<usize>)>::build(diag.severity.to_report_kind(), &diag.file, start)
+        .with_message(format!(
+            "[{}] {}",
+            diag.provider,
+            diag.rule.as_deref().unwrap_or_default()
+        ))
+        .with_label(Label::new((diag.file.as_str(), start..end)).with_message(&diag.message))
         .finish()
-        // .print((&item.file, source))
-        .write((&item.file, source), &mut buf)
+        .write((diag.file.as_str(), Source::from(highlighted)), &mut buf)
         .unwrap();
 
-        items.push(Item {
-            lines: buf
-                .split(|b| *b == b'\n')
-                .map(|slice| slice.to_vec())
-                .collect(),
-        });
+    Some(Item {
+        lines: buf.split(|b| *b == b'\n').map(|slice| slice.to_vec()).collect(),
+    })
+}
+
+/// Looks up a quick fix for `diag`, resolved lazily (on the `f` keypress)
+/// rather than precomputed for every diagnostic on every `relint`, since an
+/// LSP `codeAction` round-trip or formatter invocation per diagnostic would
+/// otherwise block every redraw: an LSP `codeAction` against whichever
+/// `lsp_clients` entry reported it, or an external formatter's diff if the
+/// provider that reported it declared one.
+fn resolve_fix(diag: &Diagnostic, config: &config::Config, lsp_clients: &mut [lsp::LspClient]) -> Option<fix::Fix> {
+    if let Some(client) = lsp_clients.iter_mut().find(|client| client.provider_name() == diag.provider) {
+        return client.code_action(&diag.file, diag).ok().flatten();
     }
 
-    items
+    let provider = config.providers.iter().find(|p| p.name == diag.provider)?;
+    let formatter = provider.formatter.as_ref()?;
+    providers::run_formatter(formatter, &diag.file)
 }
 
-fn flake8() -> Vec<Item> {
-    let out = std::process::Command::new("flake8")
-        .arg(".")
-        .output()
-        .expect("No flake8 in $PATH");
-    if out.stdout.len() == 0 {
-        return Vec::new();
+/// Runs every configured subprocess provider whose declared `extensions`
+/// overlap `changed_paths` (or every subprocess provider, if `changed_paths`
+/// is `None`, meaning the caller doesn't know what changed), merges in
+/// whatever the long-lived `lsp_clients` have published since the last
+/// tick, and renders the result. `lsp_diagnostics` and `subprocess_diagnostics`
+/// both persist across calls (keyed by file and by provider name
+/// respectively) so a provider this tick skipped doesn't lose its last
+/// report, the same way an LSP server only re-publishing diagnostics for
+/// files that actually changed doesn't lose the rest of the workspace.
+fn relint(
+    config: &config::Config,
+    lsp_clients: &[lsp::LspClient],
+    lsp_diagnostics: &mut HashMap<String, Vec<Diagnostic>>,
+    subprocess_diagnostics: &mut HashMap<String, Vec<Diagnostic>>,
+    changed_paths: Option<&[String]>,
+) -> (Vec<Diagnostic>, Vec<Item>) {
+    for provider in config
+        .providers
+        .iter()
+        .filter(|provider| !matches!(provider.parse, config::ParseMode::Lsp(_)))
+        .filter(|provider| provider_cares_about(provider, changed_paths))
+    {
+        subprocess_diagnostics.insert(provider.name.clone(), providers::run_provider(provider));
     }
-    let s = std::str::from_utf8(&out.stdout).unwrap();
-    let mut items = vec![];
-    for line in s.lines() {
-        // util/iter util.py:1:1: F821 undefined name 'f'
-        let mut tokens = line.split(':');
-        let path = tokens.next().unwrap();
-        let row = tokens.next().unwrap().parse::<usize>().unwrap();
-        let col = tokens.next().unwrap().parse::<usize>().unwrap();
-        let rest = tokens.next().unwrap();
-        let code = &rest[1..5];
-        let msg = &rest[6..];
-
-        let source = Source::from(std::fs::read_to_string(path).unwrap());
-        let offset = Location {
-            line: row - 1,
-            character: col - 1,
-        }
-        .to_byte_offset(&source);
-        let end = source.line(row - 1).unwrap().offset() + source.line(row - 1).unwrap().len();
-
-        // https://flake8.pycqa.org/en/2.6.0/warnings.html
-        let report_kind = match code.chars().next().unwrap() {
-            'E' => ReportKind::Error,
-            'W' => ReportKind::Warning,
-            'F' => ReportKind::Error, // TODO
-            'C' => ReportKind::Advice,
-            'N' => ReportKind::Warning,
-            _ => unreachable!(),
-        };
+    // Neither cache is ever told a file was deleted or renamed away (the LSP
+    // client sends no `didClose`, and a one-shot subprocess provider that's
+    // skipped this tick just keeps its last report), so prune entries for
+    // files that no longer exist before they reach `diagnostic_to_item`.
+    for diags in subprocess_diagnostics.values_mut() {
+        diags.retain(|diag| std::path::Path::new(&diag.file).exists());
+    }
+    let mut diagnostics: Vec<Diagnostic> = subprocess_diagnostics.values().flatten().cloned().collect();
 
-        let mut buf = Vec::<u8>::new();
-        ariadne::Report::<(&str, std::ops::Range<usize>)>::build(report_kind, path, offset)
-            .with_message("[flake8]")
-            .with_label(Label::new((path, offset..end)).with_message(msg))
-            .finish()
-            .write((path, source), &mut buf)
-            .unwrap();
-
-        items.push(Item {
-            lines: buf
-                .split(|b| *b == b'\n')
-                .map(|slice| slice.to_vec())
-                .collect(),
-        });
+    for client in lsp_clients {
+        for (file, diags) in client.try_recv_diagnostics() {
+            lsp_diagnostics.insert(file, diags);
+        }
     }
-    items
+    lsp_diagnostics.retain(|file, _| std::path::Path::new(file).exists());
+    diagnostics.extend(lsp_diagnostics.values().flatten().cloned());
+
+    let mut items = Vec::with_capacity(diagnostics.len());
+    diagnostics.retain(|diag| match diagnostic_to_item(diag, &config.theme) {
+        Some(item) => {
+            items.push(item);
+            true
+        }
+        None => false,
+    });
+    (diagnostics, items)
+}
+
+/// Whether `provider` should be rerun given `changed_paths`: always, if
+/// `changed_paths` is `None` (nothing to compare against yet, e.g. the
+/// initial lint) or the provider declares no `extensions` (unknown scope,
+/// so err on the side of rerunning it); otherwise only if some changed
+/// path's extension is one it declared.
+fn provider_cares_about(provider: &config::ProviderConfig, changed_paths: Option<&[String]>) -> bool {
+    let Some(changed_paths) = changed_paths else {
+        return true;
+    };
+    let Some(extensions) = &provider.extensions else {
+        return true;
+    };
+    changed_paths.iter().any(|path| {
+        std::path::Path::new(path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| extensions.iter().any(|e| e == ext))
+    })
 }
 
-fn render_in_buffer() -> Vec<Item> {
-    let mut items = pyright();
-    let items2 = flake8();
-    items.extend(items2);
-    items
+/// Spawns an `lsp::LspClient` for every `mode = "lsp"` provider in `config`
+/// and opens its configured file extensions against it. A provider whose
+/// server binary can't be spawned is dropped with a message to stderr,
+/// matching the "missing command means nothing to report" behavior of
+/// subprocess providers rather than a hard failure.
+fn spawn_lsp_clients(config: &config::Config, tx: &std::sync::mpsc::Sender<AppEvent>) -> Vec<lsp::LspClient> {
+    config
+        .providers
+        .iter()
+        .filter_map(|provider| {
+            let config::ParseMode::Lsp(lsp_config) = &provider.parse else {
+                return None;
+            };
+            match lsp::LspClient::spawn(&provider.command, &provider.args, ".", &provider.name, tx.clone()) {
+                Ok(mut client) => {
+                    for file in lsp::discover_files(std::path::Path::new("."), &lsp_config.extensions) {
+                        let _ = client.did_open(&file, &lsp_config.language_id);
+                    }
+                    Some(client)
+                }
+                Err(err) => {
+                    eprintln!("{}: {err}", provider.name);
+                    None
+                }
+            }
+        })
+        .collect()
 }
 
 struct Item {
     lines: Vec<Vec<u8>>,
 }
 
+impl Item {
+    /// Number of rendered sublines this item occupies once each hard-newline
+    /// line is word-wrapped to `width` columns (see
+    /// `ui::wrapped_subline_count`), rather than just counting hard
+    /// newlines as the scrollbar used to.
+    fn wrapped_len(&self, width: u16) -> usize {
+        self.lines
+            .iter()
+            .map(|line| ui::wrapped_subline_count(&String::from_utf8_lossy(line), width))
+            .sum()
+    }
+}
+
 #[derive(Default)]
 struct App {
+    diagnostics: Vec<Diagnostic>,
     items: Vec<Item>,
+    /// Indices into `diagnostics`/`items` that survive the current filter
+    /// and facets, in display order. `line_offsets`, `first_visible_item`
+    /// and `selected_item` all index into this, not into `items` directly.
+    visible: Vec<usize>,
     line_offsets: Vec<usize>,
     first_visible_item: usize,
     first_visible_subline: usize,
     selected_item: usize,
     width: u16,
     height: u16,
+    /// Whether `/` has put the status bar into query-editing mode.
+    filter_mode: bool,
+    filter_query: String,
+    severity_filter: Option<Severity>,
+    rule_filter: Option<String>,
+    /// Long-lived language servers, one per `mode = "lsp"` provider.
+    lsp_clients: Vec<lsp::LspClient>,
+    /// Latest diagnostics published by `lsp_clients`, by file. Persists
+    /// across `relint` calls; see `relint`'s doc comment.
+    lsp_diagnostics: HashMap<String, Vec<Diagnostic>>,
+    /// Latest diagnostics from each subprocess provider, by provider name.
+    /// Persists across `relint` calls so a `--watch` tick that skips a
+    /// provider (its `extensions` don't overlap the changed paths) doesn't
+    /// drop that provider's diagnostics from the list.
+    subprocess_diagnostics: HashMap<String, Vec<Diagnostic>>,
 }
 
 impl App {
-    fn render_to_term(&mut self, w: &mut impl Write) {
-        let mut item = self.first_visible_item;
-        let mut subline = self.first_visible_subline;
+    /// Width available for wrapping inside the list block, i.e. `width`
+    /// minus the left/right border columns `ui::draw` surrounds it with.
+    fn content_width(&self) -> u16 {
+        self.width.saturating_sub(2).max(1)
+    }
 
-        w.queue(Clear(ClearType::All)).unwrap();
-        w.queue(MoveTo(1, 1)).unwrap();
+    /// Flattened subline offset of (`item`, `subline`), or `0` if `visible`
+    /// is empty (nothing to scroll to).
+    fn line_offset(&self, item: usize, subline: usize) -> usize {
+        if self.line_offsets.is_empty() {
+            return 0;
+        }
+        self.line_offsets[item] + subline
+    }
 
-        for _row in 0..self.height {
-            if item >= self.items.len() {
-                break;
-            }
-            if subline == 0 {
-                if item == self.selected_item {
-                    w.write_all("â–· ".as_bytes()).unwrap();
-                } else {
-                    w.queue(MoveRight(3)).unwrap();
-                }
+    fn total_sublines(&self) -> usize {
+        let width = self.content_width();
+        self.visible.iter().map(|&i| self.items[i].wrapped_len(width)).sum()
+    }
 
-                w.queue(SetColors(Colors::new(Color::Black, Color::Red)))
-                    .unwrap();
-                write!(w, " {} ", item + 1).unwrap();
-                w.queue(ResetColor).unwrap();
-                write!(w, " ").unwrap();
-            } else {
-                w.queue(MoveRight(3)).unwrap();
-            }
-            w.write_all(&self.items[item].lines[subline]).unwrap();
-            w.queue(MoveToNextLine(1)).unwrap();
+    /// Rebuilds `line_offsets` from the current `visible`/`items`/`width`,
+    /// clamping `first_visible_subline` to the (possibly now-smaller)
+    /// wrapped length of `first_visible_item`. Called at the end of
+    /// `recompute_visible` and on every terminal resize, since a width change reflows every
+    /// item's wrapped subline count.
+    fn rebuild_offsets(&mut self) {
+        let width = self.content_width();
+        let mut offsets = Vec::with_capacity(self.visible.len());
+        let mut offset = 0;
+        for &i in &self.visible {
+            offsets.push(offset);
+            offset += self.items[i].wrapped_len(width);
+        }
+        self.line_offsets = offsets;
 
-            subline += 1;
-            if subline >= self.items[item].lines.len() {
-                item += 1;
-                subline = 0;
-            }
+        if let Some(&item_index) = self.visible.get(self.first_visible_item) {
+            let max_subline = self.items[item_index].wrapped_len(width).saturating_sub(1);
+            self.first_visible_subline = self.first_visible_subline.min(max_subline);
+        } else {
+            self.first_visible_subline = 0;
         }
-        w.flush().unwrap();
     }
 
-    fn line_offset(&self, item: usize, subline: usize) -> usize {
-        self.line_offsets[item] + subline
+    /// Sets `first_visible_item`/`first_visible_subline` to whatever
+    /// (item, subline) pair corresponds to the flattened subline `offset`.
+    fn set_first_visible_from_offset(&mut self, offset: usize) {
+        if self.line_offsets.is_empty() {
+            self.first_visible_item = 0;
+            self.first_visible_subline = 0;
+            return;
+        }
+        let item = match self.line_offsets.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i.saturating_sub(1),
+        };
+        self.first_visible_item = item;
+        self.first_visible_subline = offset - self.line_offsets[item];
+    }
+
+    /// Re-derives `visible`/`line_offsets` from `diagnostics` after a reload
+    /// or a change to the filter query / severity / rule facets, clamping
+    /// the selection and scroll position to stay in range.
+    fn recompute_visible(&mut self) {
+        let mut scored: Vec<(usize, i32)> = self
+            .diagnostics
+            .iter()
+            .enumerate()
+            .filter(|(_, diag)| match self.severity_filter {
+                Some(severity) => diag.severity == severity,
+                None => true,
+            })
+            .filter(|(_, diag)| match &self.rule_filter {
+                Some(rule) => diag.rule.as_deref() == Some(rule.as_str()),
+                None => true,
+            })
+            .filter_map(|(i, diag)| filter::score_diagnostic(diag, &self.filter_query).map(|score| (i, score)))
+            .collect();
+
+        if !self.filter_query.is_empty() {
+            scored.sort_by(|a, b| b.1.cmp(&a.1));
+        }
+
+        self.visible = scored.into_iter().map(|(i, _)| i).collect();
+
+        self.selected_item = self.selected_item.min(self.visible.len().saturating_sub(1));
+        self.first_visible_item = self.first_visible_item.min(self.selected_item);
+        self.first_visible_subline = 0;
+        self.rebuild_offsets();
     }
 }
 
 fn main() {
     env_logger::init();
 
-    let mut w = std::io::BufWriter::new(std::io::stdout());
-    w.queue(EnterAlternateScreen).unwrap();
-    w.queue(cursor::Hide).unwrap();
-    w.queue(DisableLineWrap).unwrap();
+    let config = config::Config::load(std::path::Path::new("devon.toml"));
+    let watch_mode = std::env::args().any(|arg| arg == "--watch");
+
+    let mut stdout = std::io::stdout();
+    stdout.queue(EnterAlternateScreen).unwrap();
+    stdout.queue(cursor::Hide).unwrap();
+    stdout.queue(DisableLineWrap).unwrap();
     enable_raw_mode().unwrap();
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout)).unwrap();
 
     let (width, height) = crossterm::terminal::size().unwrap();
+
+    let (tx, rx) = std::sync::mpsc::channel::<AppEvent>();
+    let lsp_clients = spawn_lsp_clients(&config, &tx);
+
     let mut app = App {
-        items: render_in_buffer(),
         width,
         height,
+        lsp_clients,
         ..Default::default()
     };
-    app.line_offsets = {
-        let mut offsets = vec![];
-        let mut offset = 0;
-        for item in &app.items {
-            offsets.push(offset);
-            offset += item.lines.len();
-        }
-        offsets
-    };
-    app.render_to_term(&mut w);
+    let (diagnostics, items) = relint(
+        &config,
+        &app.lsp_clients,
+        &mut app.lsp_diagnostics,
+        &mut app.subprocess_diagnostics,
+        None,
+    );
+    app.diagnostics = diagnostics;
+    app.items = items;
+    app.recompute_visible();
+    terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+
+    {
+        let tx = tx.clone();
+        std::thread::spawn(move || loop {
+            match crossterm::event::read() {
+                Ok(Event::Key(ev)) => {
+                    if tx.send(AppEvent::Key(ev)).is_err() {
+                        return;
+                    }
+                }
+                Ok(Event::Resize(width, height)) => {
+                    if tx.send(AppEvent::Resize(width, height)).is_err() {
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(_) => return,
+            }
+        });
+    }
+    let _watcher = watch_mode.then(|| watch::spawn(".", tx).expect("failed to start file watcher"));
 
-    loop {
-        match crossterm::event::read().unwrap() {
-            Event::Key(ev) => {
+    for event in rx {
+        match event {
+            AppEvent::Key(ev) => {
                 match (ev.modifiers, ev.code) {
                     (KeyModifiers::CONTROL, KeyCode::Char('c'))
                     | (KeyModifiers::CONTROL, KeyCode::Char('C')) => {
@@ -273,6 +455,29 @@ fn main() {
                     }
                     _ => {}
                 }
+                if app.filter_mode {
+                    match ev.code {
+                        KeyCode::Esc => {
+                            app.filter_mode = false;
+                            app.filter_query.clear();
+                            app.recompute_visible();
+                        }
+                        KeyCode::Enter => {
+                            app.filter_mode = false;
+                        }
+                        KeyCode::Backspace => {
+                            app.filter_query.pop();
+                            app.recompute_visible();
+                        }
+                        KeyCode::Char(c) => {
+                            app.filter_query.push(c);
+                            app.recompute_visible();
+                        }
+                        _ => {}
+                    }
+                    terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    continue;
+                }
                 if ev.modifiers != KeyModifiers::NONE {
                     continue;
                 }
@@ -284,12 +489,13 @@ fn main() {
                                 app.first_visible_item = app.selected_item;
                                 app.first_visible_subline = 0;
                             }
-                            app.render_to_term(&mut w);
+                            terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
                         }
                     }
                     KeyCode::Down => {
-                        if app.selected_item + 1 < app.items.len() {
+                        if app.selected_item + 1 < app.visible.len() {
                             app.selected_item += 1;
+                            let content_width = app.content_width();
 
                             let last_visible_offset = app
                                 .line_offset(app.first_visible_item, app.first_visible_subline)
@@ -298,7 +504,9 @@ fn main() {
 
                             let selected_last_offset = app.line_offset(
                                 app.selected_item,
-                                app.items[app.selected_item].lines.len() - 1,
+                                app.items[app.visible[app.selected_item]]
+                                    .wrapped_len(content_width)
+                                    .saturating_sub(1),
                             );
 
                             if last_visible_offset < selected_last_offset {
@@ -306,7 +514,7 @@ fn main() {
                                 for _ in 0..delta {
                                     app.first_visible_subline += 1;
                                     if app.first_visible_subline
-                                        > app.items[app.first_visible_item].lines.len() - 1
+                                        >= app.items[app.visible[app.first_visible_item]].wrapped_len(content_width)
                                     {
                                         app.first_visible_item += 1;
                                         app.first_visible_subline = 0;
@@ -314,30 +522,134 @@ fn main() {
                                 }
                             }
 
-                            app.render_to_term(&mut w);
+                            terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
                         }
                     }
                     KeyCode::Esc | KeyCode::Char('Q') | KeyCode::Char('q') => {
                         break;
                     }
                     KeyCode::PageDown => {
-                        w.queue(ScrollDown(10)).unwrap();
-                        // app.render_to_term(&mut w);
+                        let current = app.line_offset(app.first_visible_item, app.first_visible_subline);
+                        let max_offset = app.total_sublines().saturating_sub(app.height as usize);
+                        app.set_first_visible_from_offset((current + app.height as usize).min(max_offset));
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::PageUp => {
+                        let current = app.line_offset(app.first_visible_item, app.first_visible_subline);
+                        app.set_first_visible_from_offset(current.saturating_sub(app.height as usize));
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::Home => {
+                        app.first_visible_item = 0;
+                        app.first_visible_subline = 0;
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::End => {
+                        let max_offset = app.total_sublines().saturating_sub(app.height as usize);
+                        app.set_first_visible_from_offset(max_offset);
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::Char('/') => {
+                        app.filter_mode = true;
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::Char('E') => {
+                        app.severity_filter = if app.severity_filter == Some(Severity::Error) {
+                            None
+                        } else {
+                            Some(Severity::Error)
+                        };
+                        app.recompute_visible();
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::Char('W') => {
+                        app.severity_filter = if app.severity_filter == Some(Severity::Warning) {
+                            None
+                        } else {
+                            Some(Severity::Warning)
+                        };
+                        app.recompute_visible();
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::Char('R') => {
+                        let current_rule = app
+                            .visible
+                            .get(app.selected_item)
+                            .and_then(|&i| app.diagnostics[i].rule.clone());
+                        app.rule_filter = if app.rule_filter.is_some() && app.rule_filter == current_rule {
+                            None
+                        } else {
+                            current_rule
+                        };
+                        app.recompute_visible();
+                        terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                    }
+                    KeyCode::Char('f') => {
+                        if let Some(&item_index) = app.visible.get(app.selected_item) {
+                            let selected_fix =
+                                resolve_fix(&app.diagnostics[item_index], &config, &mut app.lsp_clients);
+                            if let Some(selected_fix) = selected_fix {
+                                if fix::apply_fix(&selected_fix).is_ok() {
+                                    let (diagnostics, items) = relint(
+                                        &config,
+                                        &app.lsp_clients,
+                                        &mut app.lsp_diagnostics,
+                                        &mut app.subprocess_diagnostics,
+                                        Some(std::slice::from_ref(&selected_fix.file)),
+                                    );
+                                    app.diagnostics = diagnostics;
+                                    app.items = items;
+                                    app.recompute_visible();
+                                    terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+                                }
+                            }
+                        }
                     }
                     _ => {}
                 }
             }
-            Event::Resize(width, height) => {
+            AppEvent::FilesChanged(changed_paths) => {
+                let selected_key = app
+                    .visible
+                    .get(app.selected_item)
+                    .map(|&i| (app.diagnostics[i].file.clone(), app.diagnostics[i].message.clone()));
+
+                let (diagnostics, items) = relint(
+                    &config,
+                    &app.lsp_clients,
+                    &mut app.lsp_diagnostics,
+                    &mut app.subprocess_diagnostics,
+                    Some(&changed_paths),
+                );
+                app.diagnostics = diagnostics;
+                app.items = items;
+                app.recompute_visible();
+
+                if let Some(key) = selected_key {
+                    app.selected_item = app
+                        .visible
+                        .iter()
+                        .position(|&i| (app.diagnostics[i].file.clone(), app.diagnostics[i].message.clone()) == key)
+                        .unwrap_or(0);
+                }
+                app.first_visible_item = app.first_visible_item.min(app.selected_item);
+                app.first_visible_subline = 0;
+                terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
+            }
+            AppEvent::Resize(width, height) => {
                 app.width = width;
                 app.height = height;
-                app.render_to_term(&mut w);
+                // Every item's wrapped subline count depends on `width`, so
+                // the flattened offsets need rebuilding whenever it changes.
+                app.rebuild_offsets();
+                terminal.draw(|frame| ui::draw(frame, &mut app)).unwrap();
             }
-            _ => {}
         }
     }
 
     disable_raw_mode().unwrap();
-    w.queue(cursor::Show).unwrap();
-    w.queue(LeaveAlternateScreen).unwrap();
-    w.flush().unwrap();
+    let stdout = terminal.backend_mut();
+    stdout.queue(cursor::Show).unwrap();
+    stdout.queue(LeaveAlternateScreen).unwrap();
+    stdout.flush().unwrap();
 }
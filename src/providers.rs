@@ -0,0 +1,291 @@
+//! Runs configured diagnostic providers as subprocesses and parses their
+//! output into `Diagnostic`s, per `devon.toml`.
+
+use std::process::Command;
+
+use crate::config::{FormatterConfig, JsonParseConfig, ParseMode, ProviderConfig, RegexParseConfig};
+use crate::fix::{Edit, Fix};
+use crate::{Diagnostic, Location, Range, Severity};
+
+/// Runs one provider and returns whatever diagnostics it produced.
+///
+/// A provider whose command is missing from `$PATH` is treated as having
+/// nothing to report, rather than a hard failure — this is what lets users
+/// list clippy/mypy/ruff/eslint/shellcheck in `devon.toml` without all of
+/// them being installed at once.
+pub(crate) fn run_provider(provider: &ProviderConfig) -> Vec<Diagnostic> {
+    if matches!(provider.parse, ParseMode::Lsp(_)) {
+        // LSP providers stay open across the session; see `main::spawn_lsp_clients`.
+        return Vec::new();
+    }
+
+    let output = match Command::new(&provider.command).args(&provider.args).output() {
+        Ok(out) => out,
+        Err(_) => return Vec::new(),
+    };
+    if output.stdout.is_empty() {
+        return Vec::new();
+    }
+
+    match &provider.parse {
+        ParseMode::Json(cfg) => parse_json(&output.stdout, cfg, &provider.name),
+        ParseMode::Regex(cfg) => parse_regex(&output.stdout, cfg, &provider.name),
+        ParseMode::Lsp(_) => unreachable!(),
+    }
+}
+
+/// Config authors write a dotted jq-like path (`"range.start.line"`);
+/// `serde_json::Value::pointer` wants a leading-slash JSON Pointer.
+fn json_pointer(path: &str) -> String {
+    format!("/{}", path.replace('.', "/"))
+}
+
+fn severity_from_str(s: &str) -> Severity {
+    match s {
+        "error" => Severity::Error,
+        "warning" => Severity::Warning,
+        _ => Severity::Information,
+    }
+}
+
+fn parse_json(bytes: &[u8], cfg: &JsonParseConfig, provider: &str) -> Vec<Diagnostic> {
+    let Ok(root) = serde_json::from_slice::<serde_json::Value>(bytes) else {
+        return Vec::new();
+    };
+    let Some(array) = root
+        .pointer(&json_pointer(&cfg.diagnostics_path))
+        .and_then(|v| v.as_array())
+    else {
+        return Vec::new();
+    };
+
+    array
+        .iter()
+        .filter_map(|entry| {
+            let file = entry.pointer(&json_pointer(&cfg.file_field))?.as_str()?.to_string();
+            let line = entry.pointer(&json_pointer(&cfg.line_field))?.as_u64()? as usize;
+            let character = entry
+                .pointer(&json_pointer(&cfg.character_field))?
+                .as_u64()? as usize;
+            let severity = entry
+                .pointer(&json_pointer(&cfg.severity_field))
+                .and_then(|v| v.as_str())
+                .map(severity_from_str)
+                .unwrap_or(Severity::Information);
+            let message = entry
+                .pointer(&json_pointer(&cfg.message_field))?
+                .as_str()?
+                .to_string();
+            let rule = cfg
+                .rule_field
+                .as_ref()
+                .and_then(|field| entry.pointer(&json_pointer(field)))
+                .and_then(|v| v.as_str())
+                .map(str::to_string);
+
+            Some(Diagnostic {
+                file,
+                severity,
+                message,
+                range: Range {
+                    start: Location { line, character },
+                    end: Location { line, character },
+                },
+                rule,
+                provider: provider.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn parse_regex(bytes: &[u8], cfg: &RegexParseConfig, provider: &str) -> Vec<Diagnostic> {
+    let Ok(re) = regex::Regex::new(&cfg.pattern) else {
+        return Vec::new();
+    };
+    let Ok(text) = std::str::from_utf8(bytes) else {
+        return Vec::new();
+    };
+
+    text.lines()
+        .filter_map(|line| {
+            let caps = re.captures(line)?;
+            let file = caps.name("path")?.as_str().to_string();
+            let row = caps.name("row")?.as_str().parse::<usize>().ok()?;
+            let col = caps.name("col")?.as_str().parse::<usize>().ok()?;
+            let code = caps.name("code").map(|m| m.as_str().to_string());
+            let message = caps.name("msg")?.as_str().to_string();
+
+            let severity = code
+                .as_ref()
+                .and_then(|c| c.chars().next())
+                .and_then(|first| cfg.severity_map.get(&first))
+                .map(|s| severity_from_str(s))
+                .unwrap_or(Severity::Warning);
+
+            Some(Diagnostic {
+                file,
+                severity,
+                message,
+                // The regex only carries a single point (row:col), not a
+                // range; `diagnostic_to_item` extends a zero-width range to
+                // the rest of the line when rendering.
+                range: Range {
+                    start: Location {
+                        line: row - 1,
+                        character: col - 1,
+                    },
+                    end: Location {
+                        line: row - 1,
+                        character: col - 1,
+                    },
+                },
+                rule: code,
+                provider: provider.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Runs `formatter` against `file` and parses its unified-diff output (e.g.
+/// `black --diff`) into a `Fix`, the same edit representation an LSP
+/// `codeAction` produces. Returns `None` if the formatter isn't installed,
+/// produced no output, or would change nothing.
+pub(crate) fn run_formatter(formatter: &FormatterConfig, file: &str) -> Option<Fix> {
+    let output = Command::new(&formatter.command)
+        .args(&formatter.args)
+        .arg(file)
+        .output()
+        .ok()?;
+    if output.stdout.is_empty() {
+        return None;
+    }
+
+    let diff = String::from_utf8(output.stdout).ok()?;
+    let source = std::fs::read_to_string(file).ok()?;
+    let edits = parse_unified_diff(&source, &diff);
+    if edits.is_empty() {
+        return None;
+    }
+
+    Some(Fix {
+        file: file.to_string(),
+        edits,
+    })
+}
+
+/// Turns unified-diff hunks (`@@ -start,len +start,len @@` followed by
+/// ` `/`-`/`+` lines) into byte-range `Edit`s against `source`. Each
+/// contiguous run of `-`/`+` lines between context lines becomes one edit
+/// replacing the removed lines' full byte span with the added lines' text.
+fn parse_unified_diff(source: &str, diff: &str) -> Vec<Edit> {
+    let line_starts = line_byte_starts(source);
+
+    let mut edits = Vec::new();
+    let mut old_line = 1usize;
+    let mut group_start: Option<usize> = None;
+    let mut removed_count = 0usize;
+    let mut added = String::new();
+
+    for line in diff.lines() {
+        if let Some(header) = line.strip_prefix("@@ ") {
+            flush_diff_group(&mut edits, &line_starts, source.len(), &mut group_start, &mut removed_count, &mut added);
+            old_line = parse_hunk_old_start(header).unwrap_or(old_line);
+            continue;
+        }
+        if line.starts_with("---") || line.starts_with("+++") {
+            continue;
+        }
+        match line.as_bytes().first() {
+            Some(b'-') => {
+                group_start.get_or_insert(old_line);
+                removed_count += 1;
+                old_line += 1;
+            }
+            Some(b'+') => {
+                group_start.get_or_insert(old_line);
+                added.push_str(&line[1..]);
+                added.push('\n');
+            }
+            _ => {
+                flush_diff_group(&mut edits, &line_starts, source.len(), &mut group_start, &mut removed_count, &mut added);
+                if line.starts_with(' ') {
+                    old_line += 1;
+                }
+            }
+        }
+    }
+    flush_diff_group(&mut edits, &line_starts, source.len(), &mut group_start, &mut removed_count, &mut added);
+
+    edits
+}
+
+/// Byte offset of the start of each 1-indexed source line, plus a
+/// trailing entry for `source.len()` so a hunk touching the last line
+/// doesn't read out of bounds.
+fn line_byte_starts(source: &str) -> Vec<usize> {
+    let mut starts = vec![0];
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        offset += line.len();
+        starts.push(offset);
+    }
+    starts
+}
+
+/// Parses the old-file start line out of a hunk header's body, e.g.
+/// `"-12,5 +12,6 @@ fn foo() {"` -> `12`.
+fn parse_hunk_old_start(header: &str) -> Option<usize> {
+    let old_part = header.split_whitespace().next()?;
+    old_part.strip_prefix('-')?.split(',').next()?.parse().ok()
+}
+
+/// Closes out the current run of `-`/`+` lines (if any) as one `Edit`.
+fn flush_diff_group(
+    edits: &mut Vec<Edit>,
+    line_starts: &[usize],
+    source_len: usize,
+    group_start: &mut Option<usize>,
+    removed_count: &mut usize,
+    added: &mut String,
+) {
+    if let Some(start_line) = group_start.take() {
+        if *removed_count > 0 || !added.is_empty() {
+            let start = line_starts.get(start_line - 1).copied().unwrap_or(source_len);
+            let end = line_starts.get(start_line - 1 + *removed_count).copied().unwrap_or(source_len);
+            edits.push(Edit {
+                range: start..end,
+                replacement: std::mem::take(added),
+            });
+        }
+        *removed_count = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_single_line_replacement() {
+        let source = "line1\nline2\nline3\n";
+        let diff = "--- a/file\n+++ b/file\n@@ -1,3 +1,3 @@\n line1\n-line2\n+lineX\n line3\n";
+
+        let edits = parse_unified_diff(source, diff);
+        assert_eq!(edits.len(), 1);
+
+        let patched = crate::fix::apply_edits(source, &edits).unwrap();
+        assert_eq!(patched, "line1\nlineX\nline3\n");
+    }
+
+    #[test]
+    fn parses_a_pure_addition_with_no_removed_lines() {
+        let source = "line1\nline3\n";
+        let diff = "--- a/file\n+++ b/file\n@@ -1,2 +1,3 @@\n line1\n+line2\n line3\n";
+
+        let edits = parse_unified_diff(source, diff);
+        assert_eq!(edits.len(), 1);
+
+        let patched = crate::fix::apply_edits(source, &edits).unwrap();
+        assert_eq!(patched, "line1\nline2\nline3\n");
+    }
+}
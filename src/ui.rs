@@ -0,0 +1,132 @@
+//! ratatui-based rendering.
+//!
+//! Each `Item`'s ariadne ANSI bytes are parsed into styled spans by
+//! `ansi-to-tui`, then laid out as one scrollable `Paragraph` with a
+//! `Scrollbar` tracking position in the flattened subline space. This
+//! replaces the hand-rolled cursor math and raw `ScrollDown` in the
+//! original crossterm-only renderer.
+
+use ansi_to_tui::IntoText;
+use ratatui::layout::{Constraint, Direction, Layout, Margin};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap};
+use ratatui::Frame;
+
+use crate::App;
+
+/// Draws the diagnostic list into `frame`, scrolled to
+/// `app.first_visible_item`/`app.first_visible_subline`, with a one-line
+/// status bar below it showing the filter query and active facets.
+pub(crate) fn draw(frame: &mut Frame, app: &mut App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(frame.area());
+    let list_area = chunks[0];
+    let status_area = chunks[1];
+
+    app.width = list_area.width;
+    app.height = list_area.height;
+
+    if app.visible.is_empty() {
+        let block = Block::default().borders(Borders::ALL).title("devon");
+        frame.render_widget(Paragraph::new("").block(block), list_area);
+        frame.render_widget(Paragraph::new(status_line(app)), status_area);
+        return;
+    }
+
+    let mut lines: Vec<Line> = Vec::new();
+    for (position, &item_index) in app.visible.iter().enumerate() {
+        let item = &app.items[item_index];
+        let raw = item.lines.join(&b'\n');
+        let mut text = raw
+            .into_text()
+            .unwrap_or_else(|_| Text::raw(String::from_utf8_lossy(&raw).into_owned()));
+
+        if let Some(first) = text.lines.first_mut() {
+            let marker = if position == app.selected_item { "▷ " } else { "  " };
+            first.spans.insert(0, Span::raw(marker));
+            first.spans.insert(
+                1,
+                Span::styled(
+                    format!(" {} ", item_index + 1),
+                    Style::default().fg(Color::Black).bg(Color::Red),
+                ),
+            );
+        }
+
+        lines.extend(text.lines);
+    }
+
+    let total_sublines = app.total_sublines();
+    let scroll = app.line_offset(app.first_visible_item, app.first_visible_subline) as u16;
+
+    let block = Block::default().borders(Borders::ALL).title("devon");
+    let paragraph = Paragraph::new(Text::from(lines))
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((scroll, 0));
+    frame.render_widget(paragraph, list_area);
+
+    let mut scrollbar_state = ScrollbarState::new(total_sublines).position(scroll as usize);
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight);
+    frame.render_stateful_widget(
+        scrollbar,
+        list_area.inner(Margin {
+            vertical: 1,
+            horizontal: 0,
+        }),
+        &mut scrollbar_state,
+    );
+
+    frame.render_widget(Paragraph::new(status_line(app)), status_area);
+}
+
+/// Number of sublines `line` occupies once wrapped to `width` columns,
+/// approximating the `Wrap { trim: false }` rendering above so
+/// `App`'s scroll offsets stay roughly in sync with what's on screen.
+/// Counts visible characters only (ANSI color codes are skipped), and
+/// treats every character as one column wide.
+pub(crate) fn wrapped_subline_count(line: &str, width: u16) -> usize {
+    let width = width.max(1) as usize;
+    visible_width(line).div_ceil(width).max(1)
+}
+
+/// Length of `line` in visible characters, skipping `ESC [ ... letter`
+/// ANSI CSI sequences (e.g. SGR color codes) ariadne wraps its output in.
+fn visible_width(line: &str) -> usize {
+    let mut width = 0;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+            continue;
+        }
+        width += 1;
+    }
+    width
+}
+
+fn status_line(app: &App) -> String {
+    if app.filter_mode {
+        return format!("/{}", app.filter_query);
+    }
+
+    let mut parts = vec![format!("{}/{} shown", app.visible.len(), app.items.len())];
+    if !app.filter_query.is_empty() {
+        parts.push(format!("filter: {}", app.filter_query));
+    }
+    if let Some(severity) = app.severity_filter {
+        parts.push(format!("severity: {}", severity.label()));
+    }
+    if let Some(rule) = &app.rule_filter {
+        parts.push(format!("rule: {rule}"));
+    }
+    parts.join("  ")
+}
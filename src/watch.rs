@@ -0,0 +1,56 @@
+//! Filesystem watching for `--watch` mode.
+//!
+//! Wraps a `notify` watcher and debounces bursts of change events (e.g. an
+//! editor writing a file in several small operations) into a single
+//! `AppEvent::FilesChanged` tick, so rapid saves don't thrash the
+//! subprocess-based providers.
+
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+use notify::{RecursiveMode, Watcher};
+
+use crate::AppEvent;
+
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Watches `root` recursively and forwards a debounced `FilesChanged` event
+/// to `tx`, carrying every path that changed, whenever something under it
+/// is modified.
+///
+/// The returned `Watcher` must be kept alive for the duration of the watch;
+/// dropping it stops the watch.
+pub(crate) fn spawn(root: &str, tx: Sender<AppEvent>) -> notify::Result<impl Watcher> {
+    let (raw_tx, raw_rx) = std::sync::mpsc::channel();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        if let Ok(event) = res {
+            for path in event.paths {
+                let _ = raw_tx.send(path);
+            }
+        }
+    })?;
+    watcher.watch(Path::new(root), RecursiveMode::Recursive)?;
+
+    std::thread::spawn(move || loop {
+        let Ok(first) = raw_rx.recv() else {
+            return;
+        };
+        // Drain whatever else arrives within the debounce window so a burst
+        // of saves collapses into one re-lint.
+        let mut changed = std::collections::HashSet::new();
+        changed.insert(first);
+        while let Ok(path) = raw_rx.recv_timeout(DEBOUNCE) {
+            changed.insert(path);
+        }
+        let changed = changed
+            .into_iter()
+            .filter_map(|path| path.to_str().map(str::to_string))
+            .collect();
+        if tx.send(AppEvent::FilesChanged(changed)).is_err() {
+            return;
+        }
+    });
+
+    Ok(watcher)
+}